@@ -0,0 +1,170 @@
+//! The gRPC client.
+
+use std::fmt;
+#[cfg(feature = "tls")]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use http::Uri;
+#[cfg(feature = "tls")]
+use http::{Request, Response};
+#[cfg(feature = "tls")]
+use hyper::{body::Incoming, client::conn::http2};
+#[cfg(feature = "tls")]
+use hyper_util::rt::TokioExecutor;
+#[cfg(feature = "tls")]
+use tonic::body::BoxBody;
+#[cfg(feature = "tls")]
+use tower::Service;
+
+mod service;
+
+#[cfg(feature = "tls")]
+use service::{ProxyConfig, TlsConnector};
+#[cfg(feature = "tls")]
+use tokio::net::TcpStream;
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::ClientConfig;
+
+/// A gRPC client channel, connected to a single [`Endpoint`].
+///
+/// Obtained via [`Endpoint::connect`]. Implements [`tower::Service`] so it
+/// can be used directly as a `tonic` client transport.
+pub struct Channel {
+    #[cfg(feature = "tls")]
+    sender: http2::SendRequest<BoxBody>,
+}
+
+impl fmt::Debug for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Channel").finish()
+    }
+}
+
+#[cfg(feature = "tls")]
+impl Service<Request<BoxBody>> for Channel {
+    type Response = Response<Incoming>;
+    type Error = crate::BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.sender.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<BoxBody>) -> Self::Future {
+        // `SendRequest` is a cheap handle onto the shared connection; cloning
+        // it lets the returned future outlive this `&mut self` call, which
+        // is what `tower::Service` requires once `poll_ready` has resolved.
+        let mut sender = self.sender.clone();
+        Box::pin(async move { sender.send_request(req).await.map_err(Into::into) })
+    }
+}
+
+/// Configures and establishes a [`Channel`] to a single gRPC endpoint.
+pub struct Endpoint {
+    uri: Uri,
+    #[cfg(feature = "tls")]
+    tls: Option<(ClientConfig, bool)>,
+    #[cfg(feature = "tls")]
+    proxy: Option<(Uri, ProxyConfig)>,
+}
+
+impl Endpoint {
+    /// Creates an [`Endpoint`] for the given URI, e.g. `https://example.com:443`.
+    pub fn new(uri: Uri) -> Self {
+        Self {
+            uri,
+            #[cfg(feature = "tls")]
+            tls: None,
+            #[cfg(feature = "tls")]
+            proxy: None,
+        }
+    }
+
+    /// Enables TLS for this endpoint, using `config` to perform the client
+    /// handshake. `assume_http2` allows the connection to proceed even if
+    /// ALPN doesn't negotiate `h2`, exactly as [`Endpoint::connect`]
+    /// requires.
+    #[cfg(feature = "tls")]
+    pub fn tls_config(mut self, config: ClientConfig, assume_http2: bool) -> Self {
+        self.tls = Some((config, assume_http2));
+        self
+    }
+
+    /// Routes the connection through an HTTP forward proxy at `proxy_uri`:
+    /// before the TLS handshake, a `CONNECT` request is issued to the proxy
+    /// asking it to tunnel to this endpoint's host and port. SNI and
+    /// certificate verification still use this endpoint's own URI.
+    #[cfg(feature = "tls")]
+    pub fn via_proxy(mut self, proxy_uri: Uri) -> Self {
+        self.proxy = Some((proxy_uri, ProxyConfig::new()));
+        self
+    }
+
+    /// Sets the `Proxy-Authorization` header to HTTP Basic auth for the
+    /// given credentials. Has no effect unless [`Endpoint::via_proxy`] has
+    /// already been called; a later `via_proxy` call discards them.
+    #[cfg(feature = "tls")]
+    pub fn proxy_basic_auth(mut self, username: &str, password: &str) -> Self {
+        if let Some((proxy_uri, proxy)) = self.proxy.take() {
+            self.proxy = Some((proxy_uri, proxy.basic_auth(username, password)));
+        }
+        self
+    }
+
+    /// Connects to the endpoint, performing the TLS handshake (and, if
+    /// [`Endpoint::via_proxy`] was set, the proxy `CONNECT` tunnel first).
+    #[cfg(feature = "tls")]
+    pub async fn connect(&self) -> Result<Channel, crate::BoxError> {
+        let (config, assume_http2) = self
+            .tls
+            .clone()
+            .ok_or("Endpoint::connect requires Endpoint::tls_config to be set")?;
+        let domain = self.uri.host().ok_or("endpoint URI has no host")?;
+        let target_authority = self
+            .uri
+            .authority()
+            .ok_or("endpoint URI has no authority")?
+            .as_str();
+
+        let mut connector = TlsConnector::new(config, domain, assume_http2)?;
+        let dial_authority = match &self.proxy {
+            Some((proxy_uri, proxy_config)) => {
+                connector = connector.with_proxy(proxy_config.clone(), target_authority);
+                proxy_uri
+                    .authority()
+                    .ok_or("proxy URI has no authority")?
+                    .as_str()
+            }
+            None => target_authority,
+        };
+
+        let tcp = TcpStream::connect(dial_authority).await?;
+        let io = connector.connect(tcp).await?;
+
+        let (sender, connection) = http2::handshake(TokioExecutor::new(), io).await?;
+        tokio::spawn(async move {
+            if let Err(error) = connection.await {
+                tracing::debug!(%error, "client connection task failed");
+            }
+        });
+
+        Ok(Channel { sender })
+    }
+}
+
+impl fmt::Debug for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Endpoint");
+        s.field("uri", &self.uri);
+        #[cfg(feature = "tls")]
+        {
+            s.field("tls", &self.tls.is_some());
+            s.field("proxied", &self.proxy.is_some());
+        }
+        s.finish()
+    }
+}
@@ -0,0 +1,48 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use hyper::rt;
+
+/// A type-erased client I/O stream, produced once the TLS handshake (and any
+/// proxy tunnelling) has completed.
+pub(crate) struct BoxedIo(Pin<Box<dyn IoStream>>);
+
+impl BoxedIo {
+    pub(crate) fn new<I: rt::Read + rt::Write + Send + 'static>(io: I) -> Self {
+        Self(Box::pin(io))
+    }
+}
+
+impl rt::Read for BoxedIo {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: rt::ReadBufCursor<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl rt::Write for BoxedIo {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+trait IoStream: rt::Read + rt::Write + Send {}
+impl<T: rt::Read + rt::Write + Send> IoStream for T {}
@@ -0,0 +1,13 @@
+#[cfg(feature = "tls")]
+mod io;
+#[cfg(feature = "tls")]
+mod proxy;
+#[cfg(feature = "tls")]
+mod tls;
+
+#[cfg(feature = "tls")]
+pub(crate) use io::BoxedIo;
+#[cfg(feature = "tls")]
+pub(crate) use proxy::ProxyConfig;
+#[cfg(feature = "tls")]
+pub(crate) use tls::TlsConnector;
@@ -0,0 +1,250 @@
+use std::{
+    fmt, io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use pin_project::pin_project;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+
+/// Configuration for tunnelling a connection through an HTTP forward proxy
+/// via the `CONNECT` method, before the TLS handshake begins.
+#[derive(Clone)]
+pub(crate) struct ProxyConfig {
+    basic_auth: Option<Arc<str>>,
+}
+
+impl ProxyConfig {
+    pub(crate) fn new() -> Self {
+        Self { basic_auth: None }
+    }
+
+    /// Sets the `Proxy-Authorization` header to HTTP Basic auth for the
+    /// given credentials.
+    pub(crate) fn basic_auth(mut self, username: &str, password: &str) -> Self {
+        let credentials = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            format!("{username}:{password}"),
+        );
+        self.basic_auth = Some(format!("Basic {credentials}").into());
+        self
+    }
+}
+
+impl fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field(
+                "basic_auth",
+                &self.basic_auth.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+/// Issues a `CONNECT` request to `io` (which must already be connected to
+/// the proxy) asking it to tunnel to `target_authority` (a `host:port`
+/// pair), and returns `io` ready for the TLS handshake with the target once
+/// the proxy responds with a `2xx` status.
+pub(crate) async fn connect<IO>(
+    proxy: &ProxyConfig,
+    target_authority: &str,
+    mut io: IO,
+) -> Result<PrefixedIo<IO>, crate::BoxError>
+where
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut request =
+        format!("CONNECT {target_authority} HTTP/1.1\r\nHost: {target_authority}\r\n");
+    if let Some(auth) = &proxy.basic_auth {
+        request.push_str(&format!("Proxy-Authorization: {auth}\r\n"));
+    }
+    request.push_str("\r\n");
+
+    io.write_all(request.as_bytes()).await?;
+    io.flush().await?;
+
+    let (head, leftover) = read_response_head(&mut io).await?;
+    let status = parse_status_code(&head)?;
+    if !(200..300).contains(&status) {
+        return Err(
+            format!("proxy CONNECT to {target_authority} failed with status {status}").into(),
+        );
+    }
+
+    Ok(PrefixedIo::new(leftover, io))
+}
+
+/// Reads the proxy's response head (up to and including the blank line that
+/// terminates the headers) in chunks rather than a syscall per byte, and
+/// returns it split from whatever was read past the terminator — the start
+/// of the tunnelled stream, which must be replayed rather than dropped.
+async fn read_response_head<IO: AsyncRead + Unpin>(
+    io: &mut IO,
+) -> Result<(Vec<u8>, Vec<u8>), crate::BoxError> {
+    const MAX_RESPONSE_HEAD: usize = 8 * 1024;
+    const CHUNK: usize = 512;
+
+    let mut head = Vec::new();
+    let mut chunk = [0u8; CHUNK];
+    loop {
+        let n = io.read(&mut chunk).await?;
+        if n == 0 {
+            return Err("proxy closed the connection before a CONNECT response".into());
+        }
+        head.extend_from_slice(&chunk[..n]);
+
+        if let Some(end) = head
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .map(|start| start + 4)
+        {
+            let leftover = head.split_off(end);
+            return Ok((head, leftover));
+        }
+
+        if head.len() > MAX_RESPONSE_HEAD {
+            return Err("proxy CONNECT response head too large".into());
+        }
+    }
+}
+
+/// Wraps `io` so reads first replay `prefix` — bytes already consumed from
+/// `io` while scanning for something else that must not be lost, such as the
+/// bytes read past a proxy CONNECT response's terminator — before delegating to
+/// `io` itself. Writes pass straight through.
+#[pin_project]
+pub(crate) struct PrefixedIo<IO> {
+    prefix: Vec<u8>,
+    prefix_pos: usize,
+    #[pin]
+    io: IO,
+}
+
+impl<IO> PrefixedIo<IO> {
+    pub(crate) fn new(prefix: Vec<u8>, io: IO) -> Self {
+        Self {
+            prefix,
+            prefix_pos: 0,
+            io,
+        }
+    }
+}
+
+impl<IO: AsyncRead> AsyncRead for PrefixedIo<IO> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+
+        if *this.prefix_pos < this.prefix.len() {
+            let remaining = &this.prefix[*this.prefix_pos..];
+            let n = remaining.len().min(buf.remaining());
+            buf.put_slice(&remaining[..n]);
+            *this.prefix_pos += n;
+            return Poll::Ready(Ok(()));
+        }
+
+        this.io.poll_read(cx, buf)
+    }
+}
+
+impl<IO: AsyncWrite> AsyncWrite for PrefixedIo<IO> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.project().io.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().io.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().io.poll_shutdown(cx)
+    }
+}
+
+fn parse_status_code(response: &[u8]) -> Result<u16, crate::BoxError> {
+    let status_line = std::str::from_utf8(response)?
+        .lines()
+        .next()
+        .ok_or("empty proxy CONNECT response")?;
+
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .ok_or("malformed proxy CONNECT status line")?
+        .parse::<u16>()
+        .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_status_code_accepts_well_formed_line() {
+        assert_eq!(
+            parse_status_code(b"HTTP/1.1 200 Connection Established\r\n").unwrap(),
+            200
+        );
+    }
+
+    #[test]
+    fn parse_status_code_rejects_malformed_status_line() {
+        assert!(parse_status_code(b"not a status line\r\n").is_err());
+        assert!(parse_status_code(b"").is_err());
+    }
+
+    #[tokio::test]
+    async fn connect_rejects_non_2xx_status() {
+        let (mut proxy, client) = tokio::io::duplex(1024);
+        let request = async {
+            proxy
+                .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+                .await
+                .unwrap();
+        };
+        let (result, _) = tokio::join!(
+            connect(&ProxyConfig::new(), "example.com:443", client),
+            request
+        );
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn read_response_head_preserves_bytes_read_past_the_terminator() {
+        let (mut proxy, mut client) = tokio::io::duplex(1024);
+        let write = async move {
+            // The tunnelled stream's first bytes arrive in the same chunk as
+            // the response head's terminator; a chunked read will pull both
+            // in together and must hand the latter back rather than drop it.
+            proxy
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\nTLS-START")
+                .await
+                .unwrap();
+        };
+        let (result, _) = tokio::join!(read_response_head(&mut client), write);
+        let (head, leftover) = result.unwrap();
+        assert!(head.ends_with(b"\r\n\r\n"));
+        assert_eq!(leftover, b"TLS-START");
+    }
+
+    #[tokio::test]
+    async fn read_response_head_rejects_oversized_head() {
+        let (mut proxy, mut client) = tokio::io::duplex(16 * 1024);
+        let filler = async move {
+            // Never terminated by `\r\n\r\n`, so the 8KiB guard is the only
+            // thing that can stop `read_response_head` from reading forever.
+            proxy.write_all(&vec![b'a'; 9 * 1024]).await.unwrap();
+        };
+        let (result, _) = tokio::join!(read_response_head(&mut client), filler);
+        assert!(result.is_err());
+    }
+}
@@ -9,6 +9,7 @@ use tokio_rustls::{
 };
 
 use super::io::BoxedIo;
+use super::proxy::{self, PrefixedIo, ProxyConfig};
 use crate::service::ALPN_H2;
 
 #[derive(Clone)]
@@ -16,6 +17,7 @@ pub(crate) struct TlsConnector {
     config: Arc<ClientConfig>,
     domain: Arc<ServerName<'static>>,
     assume_http2: bool,
+    proxy: Option<(ProxyConfig, Arc<str>)>,
 }
 
 impl TlsConnector {
@@ -30,13 +32,33 @@ impl TlsConnector {
             config: Arc::new(config),
             domain: Arc::new(ServerName::try_from(domain)?.to_owned()),
             assume_http2,
+            proxy: None,
         })
     }
 
+    /// Routes the connection through an HTTP forward proxy: before the TLS
+    /// handshake, a `CONNECT` request is issued to the proxy asking it to
+    /// tunnel to `target_authority` (the endpoint's `host:port`). SNI and
+    /// certificate verification still use the `domain` passed to
+    /// [`Self::new`].
+    pub(crate) fn with_proxy(
+        mut self,
+        proxy: ProxyConfig,
+        target_authority: impl Into<Arc<str>>,
+    ) -> Self {
+        self.proxy = Some((proxy, target_authority.into()));
+        self
+    }
+
     pub(crate) async fn connect<I>(&self, io: I) -> Result<BoxedIo, crate::BoxError>
     where
         I: AsyncRead + AsyncWrite + Send + Unpin + 'static,
     {
+        let io = match &self.proxy {
+            Some((proxy, target_authority)) => proxy::connect(proxy, target_authority, io).await?,
+            None => PrefixedIo::new(Vec::new(), io),
+        };
+
         let io = RustlsConnector::from(self.config.clone())
             .connect(self.domain.as_ref().to_owned(), io)
             .await?;
@@ -54,6 +76,8 @@ impl TlsConnector {
 
 impl fmt::Debug for TlsConnector {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("TlsConnector").finish()
+        f.debug_struct("TlsConnector")
+            .field("proxied", &self.proxy.is_some())
+            .finish()
     }
 }
@@ -30,7 +30,7 @@ pub use self::error::Error;
 pub(crate) use self::error::BoxError;
 #[doc(inline)]
 #[cfg(feature = "server")]
-pub use self::server::Server;
+pub use self::server::{ConnectInfo, Server};
 
 pub use hyper::{body::Body, Uri};
 #[cfg(feature = "tls")]
@@ -1,38 +1,698 @@
 use std::{
+    fmt,
+    future::Future,
     io,
     net::{SocketAddr, TcpListener as StdTcpListener},
     ops::ControlFlow,
     pin::{pin, Pin},
+    sync::Arc,
     task::{ready, Context, Poll},
     time::Duration,
 };
 
+use http::Request;
+use pin_project::pin_project;
 use tokio::{
-    io::{AsyncRead, AsyncWrite},
+    io::{AsyncRead, AsyncWrite, ReadBuf},
     net::{TcpListener, TcpStream},
 };
 use tokio_stream::wrappers::TcpListenerStream;
 use tokio_stream::{Stream, StreamExt};
+use tower::{Layer, Service};
 use tracing::warn;
 
 use super::service::ServerIo;
 #[cfg(feature = "tls")]
 use super::service::TlsAcceptor;
+#[cfg(feature = "tls")]
+use crate::CertificateDer;
+
+/// Information about the accepted connection that produced a request.
+///
+/// [`ConnectInfoLayer`] inserts this into each request's extensions before
+/// dispatch, so handlers can recover the peer's socket address and, for TLS
+/// connections, the client's certificate chain (e.g. to implement
+/// mTLS-based authorization) via `req.extensions().get::<ConnectInfo>()`.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectInfo {
+    remote_addr: Option<SocketAddr>,
+    #[cfg(feature = "tls")]
+    alpn_protocol: Option<Vec<u8>>,
+    #[cfg(feature = "tls")]
+    certificates: Option<Arc<Vec<CertificateDer<'static>>>>,
+}
+
+impl ConnectInfo {
+    /// The remote peer's socket address, if known.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// The ALPN protocol negotiated during the TLS handshake, if any.
+    #[cfg(feature = "tls")]
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// The certificate chain presented by the client during the TLS
+    /// handshake, if the client presented one.
+    #[cfg(feature = "tls")]
+    pub fn peer_certificates(&self) -> Option<Arc<Vec<CertificateDer<'static>>>> {
+        self.certificates.clone()
+    }
+}
+
+/// Implemented by connection types that can report the peer address they
+/// were accepted from.
+///
+/// This is analogous to tonic's own `Connected` trait; it lets
+/// [`tcp_incoming`] build a [`ConnectInfo`] without depending on a concrete
+/// transport. Implement it for your own I/O type's [`Listener::Io`] to use a
+/// custom transport with [`Server::serve_with_incoming`](super::Server::serve_with_incoming).
+pub trait Connected {
+    /// Reports what can be known about the peer at accept time (e.g. its
+    /// socket address). Transports with nothing to report, like Unix domain
+    /// sockets, can return [`ConnectInfo::default()`].
+    fn connect_info(&self) -> ConnectInfo;
+}
+
+impl Connected for TcpStream {
+    fn connect_info(&self) -> ConnectInfo {
+        ConnectInfo {
+            remote_addr: self.peer_addr().ok(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A shared signal that graceful shutdown has begun.
+///
+/// Cloning shares the same underlying signal; [`trigger`](Self::trigger) is
+/// idempotent and wakes every connection waiting on it. Pass a clone to
+/// [`Server::serve_with_shutdown`](super::Server::serve_with_shutdown) and
+/// keep another to call `trigger` on (e.g. once a Ctrl+C future resolves)
+/// when it's time to start draining.
+#[derive(Clone)]
+pub struct ShutdownSignal {
+    triggered: Arc<std::sync::atomic::AtomicBool>,
+    notify: Arc<tokio::sync::Notify>,
+}
+
+impl Default for ShutdownSignal {
+    fn default() -> Self {
+        Self {
+            triggered: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            notify: Arc::new(tokio::sync::Notify::new()),
+        }
+    }
+}
+
+impl fmt::Debug for ShutdownSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShutdownSignal")
+            .field("triggered", &self.is_triggered())
+            .finish()
+    }
+}
+
+impl ShutdownSignal {
+    /// Creates a new signal that has not yet been triggered.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Begins graceful shutdown: every connection sharing this signal starts
+    /// draining. Idempotent, and safe to call more than once.
+    pub fn trigger(&self) {
+        self.triggered
+            .store(true, std::sync::atomic::Ordering::Release);
+        self.notify.notify_waiters();
+    }
+
+    fn is_triggered(&self) -> bool {
+        self.triggered.load(std::sync::atomic::Ordering::Acquire)
+    }
+}
+
+/// Tracks requests currently being served on a connection.
+///
+/// This lets [`CancellableIo`] tell a connection that is genuinely idle
+/// apart from one that is mid-request: both can be parked waiting on the
+/// socket, but only the former is safe to cut once shutdown is signalled.
+///
+/// [`ServerIo::active_requests`](super::service::ServerIo::active_requests)
+/// hands out the clone that `Server::serve_with_incoming` wraps each
+/// connection's service with [`ActiveRequestsLayer`] to call
+/// [`guard`](Self::guard) on for every request it dispatches.
+#[derive(Clone, Default)]
+pub(crate) struct ActiveRequests {
+    count: Arc<std::sync::atomic::AtomicUsize>,
+    idle: Arc<tokio::sync::Notify>,
+}
+
+impl ActiveRequests {
+    /// Marks one request as in flight on the connection until the returned
+    /// guard is dropped.
+    pub(crate) fn guard(&self) -> RequestGuard {
+        self.count.fetch_add(1, std::sync::atomic::Ordering::AcqRel);
+        RequestGuard(self.clone())
+    }
+
+    fn any(&self) -> bool {
+        self.count.load(std::sync::atomic::Ordering::Acquire) != 0
+    }
+
+    /// Builds a future that resolves the next time a request finishes on
+    /// this connection.
+    ///
+    /// Uses [`EnabledNotified`]: this is constructed and polled for the
+    /// first time in the same [`CancellableIo::poll_read`] call, so a
+    /// [`RequestGuard::drop`] on another task racing that gap must already
+    /// be registered for, not registered by, that first poll.
+    fn idle_notified(&self) -> Pin<Box<EnabledNotified>> {
+        EnabledNotified::new(self.idle.clone())
+    }
+}
+
+/// Keeps a request counted as in flight on a [`CancellableIo`] until dropped.
+pub(crate) struct RequestGuard(ActiveRequests);
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.0
+            .count
+            .fetch_sub(1, std::sync::atomic::Ordering::AcqRel);
+        // Wake anything parked in `CancellableIo::poll_read` waiting for
+        // this connection to go idle so shutdown, if already signalled,
+        // is acted on promptly instead of waiting for more real I/O.
+        self.0.idle.notify_waiters();
+    }
+}
+
+/// Wraps a connection's request-dispatch service so every request counts
+/// toward its connection's [`ActiveRequests`] for as long as it is being
+/// served.
+///
+/// Applying this once per connection (alongside [`ConnectInfoLayer`]) is
+/// what actually marks requests in flight for [`CancellableIo`] to consult:
+/// without it, [`ActiveRequests::any`] always reports `false` and a
+/// mid-request connection is cut the instant shutdown fires.
+#[derive(Clone)]
+pub(crate) struct ActiveRequestsLayer {
+    active_requests: ActiveRequests,
+}
+
+impl ActiveRequestsLayer {
+    pub(crate) fn new(active_requests: ActiveRequests) -> Self {
+        Self { active_requests }
+    }
+}
+
+impl<S> Layer<S> for ActiveRequestsLayer {
+    type Service = ActiveRequestsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ActiveRequestsService {
+            inner,
+            active_requests: self.active_requests.clone(),
+        }
+    }
+}
+
+/// See [`ActiveRequestsLayer`].
+#[derive(Clone)]
+pub(crate) struct ActiveRequestsService<S> {
+    inner: S,
+    active_requests: ActiveRequests,
+}
+
+impl<S, Req> Service<Req> for ActiveRequestsService<S>
+where
+    S: Service<Req>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ActiveRequestFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        ActiveRequestFuture {
+            inner: self.inner.call(req),
+            _guard: self.active_requests.guard(),
+        }
+    }
+}
+
+/// Holds a [`RequestGuard`] for as long as the wrapped response future
+/// hasn't resolved, so a request counts as in flight until its response is
+/// fully produced.
+#[pin_project]
+pub(crate) struct ActiveRequestFuture<F> {
+    #[pin]
+    inner: F,
+    _guard: RequestGuard,
+}
+
+impl<F: Future> Future for ActiveRequestFuture<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.project().inner.poll(cx)
+    }
+}
+
+/// An owned wait on an [`Arc`]-shared [`Notify`](tokio::sync::Notify),
+/// registered as a waiter at construction time rather than on first poll.
+///
+/// `notify_waiters()` stores no permit for a waiter that hasn't started
+/// waiting yet, so a plain `async move { notify.notified().await }`, boxed
+/// to let a struct field hold it across polls, only registers once
+/// something actually polls it — which may not happen until well after
+/// construction, leaving a gap where a `notify_waiters()` call is missed
+/// entirely. Pinning and calling `enable()` up front, the same way the
+/// accept loop does with a local `tokio::pin!`, closes that gap; this type
+/// exists because both [`CancellableIo`]'s shutdown wait and
+/// [`ActiveRequests`]'s idle wait need to survive as struct fields across
+/// many `poll_read` calls rather than a single local.
+struct EnabledNotified {
+    // Declared *before* `notify`: fields drop in declaration order, and
+    // `notified` borrows `*notify`, so it must be torn down first.
+    notified: Pin<Box<tokio::sync::Notified<'static>>>,
+    // Kept alive for as long as `notified` borrows from it; the `Arc`'s
+    // heap allocation — not this field's own address — is what `notified`
+    // actually points into, so it stays valid across any move of `Self`.
+    notify: Arc<tokio::sync::Notify>,
+}
+
+impl EnabledNotified {
+    fn new(notify: Arc<tokio::sync::Notify>) -> Pin<Box<Self>> {
+        // SAFETY: `notified` borrows `*notify`, which lives behind this same
+        // `Arc` and is never dropped or replaced before `notified` is (both
+        // are fields of this same, never-destructured struct, and declared
+        // in the drop order that guarantees it). Extending the borrow to
+        // `'static` is sound for the reason given on the `notify` field
+        // above.
+        let notified: tokio::sync::Notified<'static> =
+            unsafe { std::mem::transmute(notify.notified()) };
+        let mut notified = Box::pin(notified);
+        notified.as_mut().enable();
+        Box::pin(Self { notified, notify })
+    }
+}
+
+impl Future for EnabledNotified {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // SAFETY: neither field is ever moved out of `self`.
+        unsafe { self.get_unchecked_mut() }.notified.as_mut().poll(cx)
+    }
+}
+
+/// Wraps an accepted connection so that, once shutdown has been signalled
+/// and no request is in flight on it, it yields EOF on the next read instead
+/// of delegating to the underlying I/O.
+///
+/// Idle keep-alive connections close promptly because the wrapper also polls
+/// a [`Notify`](tokio::sync::Notify) so it wakes up as soon as shutdown
+/// fires, rather than waiting for the next bit of real I/O. A connection
+/// that is mid-request is unaffected, since it is only cut once
+/// [`ActiveRequests`] (populated by [`ActiveRequestsLayer`]) reports none in
+/// flight; once shutdown has fired, the wrapper also parks on
+/// `ActiveRequests`'s own notification so the last request finishing wakes
+/// it immediately rather than waiting on more real I/O that may never
+/// arrive. Writes are never interrupted, so in-flight responses are always
+/// allowed to finish.
+///
+/// Also holds the [`ConnectionGuard`] for the connection it wraps, so the
+/// live-connection count stays accurate for as long as the connection is
+/// reachable through any layer built on top of this `IO`, not just while the
+/// innermost `Accepted` value is held.
+///
+/// The guard starts out unset: a connection is only counted once it is
+/// actually established (see [`Self::establish`]), not merely accepted, so a
+/// connection stuck in a TLS handshake never holds capacity that a finished
+/// one needs freed.
+#[pin_project]
+pub(crate) struct CancellableIo<IO> {
+    #[pin]
+    inner: IO,
+    shutdown: ShutdownSignal,
+    active_requests: ActiveRequests,
+    notified: Pin<Box<EnabledNotified>>,
+    /// Parked on `active_requests`'s idle notification once shutdown has
+    /// fired but a request is still in flight; re-armed (see
+    /// [`ActiveRequests::idle_notified`]) each time it resolves, since a
+    /// single request finishing doesn't necessarily mean all of them have.
+    idle_notified: Option<Pin<Box<EnabledNotified>>>,
+    _guard: Option<ConnectionGuard>,
+}
+
+impl<IO> CancellableIo<IO> {
+    fn new(inner: IO, shutdown: ShutdownSignal) -> Self {
+        let notified = EnabledNotified::new(shutdown.notify.clone());
+        Self {
+            inner,
+            shutdown,
+            active_requests: ActiveRequests::default(),
+            notified,
+            idle_notified: None,
+            _guard: None,
+        }
+    }
+
+    /// Marks the connection as established, attaching `guard` so it counts
+    /// toward `max_connections` for as long as the connection is reachable
+    /// through any layer built on top of this `IO`.
+    fn establish(&mut self, guard: ConnectionGuard) {
+        self._guard = Some(guard);
+    }
+
+    /// Returns a handle for tracking requests in flight on this connection.
+    pub(crate) fn active_requests(&self) -> ActiveRequests {
+        self.active_requests.clone()
+    }
+}
+
+impl<IO: Connected> Connected for CancellableIo<IO> {
+    fn connect_info(&self) -> ConnectInfo {
+        self.inner.connect_info()
+    }
+}
+
+impl<IO: AsyncRead> AsyncRead for CancellableIo<IO> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+
+        if this.shutdown.is_triggered() {
+            // While a request is still being served, stay parked on
+            // `active_requests`'s idle notification too, so the moment the
+            // last guard drops this task is woken to recheck and close
+            // promptly, rather than only reacting to the next bit of real
+            // I/O (which an otherwise-idle connection may never produce
+            // again). The wait is armed before the count is checked, so a
+            // guard dropping in between is never missed, and re-armed each
+            // time it resolves while requests remain in flight.
+            loop {
+                if this.idle_notified.is_none() {
+                    *this.idle_notified = Some(this.active_requests.idle_notified());
+                }
+                if !this.active_requests.any() {
+                    return Poll::Ready(Ok(()));
+                }
+                let idle_notified = this.idle_notified.as_mut().expect("just inserted");
+                if idle_notified.as_mut().poll(cx).is_pending() {
+                    break;
+                }
+                *this.idle_notified = None;
+            }
+        } else if this.notified.as_mut().poll(cx).is_ready() && !this.active_requests.any() {
+            // Shutdown was just signalled and nothing is in flight right
+            // now: close promptly instead of waiting for more real I/O.
+            return Poll::Ready(Ok(()));
+        }
+
+        this.inner.poll_read(cx, buf)
+    }
+}
+
+impl<IO: AsyncWrite> AsyncWrite for CancellableIo<IO> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+}
+
+/// Accept-loop backpressure knobs.
+///
+/// `max_concurrent_handshakes` bounds how many TLS handshakes may be in
+/// flight at once; once reached, the accept loop stops polling the listener
+/// until a handshake completes. `max_connections` bounds the number of live
+/// (established, already handed off to the service) connections; once
+/// reached, the accept loop pauses entirely until enough connections close
+/// (see [`RESUME_WATERMARK_FRACTION`]). A
+/// connection only counts toward this limit once its handshake finishes, so
+/// the live count can transiently exceed it by however many handshakes were
+/// already in flight when the limit was hit; combine with
+/// `max_concurrent_handshakes` to bound that overshoot.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct AcceptLimits {
+    pub(crate) max_concurrent_handshakes: Option<usize>,
+    pub(crate) max_connections: Option<usize>,
+}
+
+/// The fraction of `max_connections` that must close before the accept loop
+/// resumes polling the listener again, once the limit was hit.
+///
+/// Resuming as soon as a single connection closes would thrash between
+/// pausing and resuming under sustained load at the limit; waiting for a
+/// margin proportional to the limit smooths that out regardless of how low
+/// `max_connections` is set. A flat margin would, for any limit at or below
+/// the margin, collapse the resume threshold down to 1 and drain the
+/// connection count to almost nothing before resuming.
+const RESUME_WATERMARK_FRACTION: usize = 8;
+
+/// Tracks the number of live connections handed off by the accept loop and
+/// wakes the loop up once enough of them have closed to resume accepting.
+#[derive(Clone)]
+struct ConnectionTracker {
+    count: Arc<std::sync::atomic::AtomicUsize>,
+    resumed: Arc<tokio::sync::Notify>,
+}
+
+impl ConnectionTracker {
+    fn new() -> Self {
+        Self {
+            count: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            resumed: Arc::new(tokio::sync::Notify::new()),
+        }
+    }
+
+    fn live(&self) -> usize {
+        self.count.load(std::sync::atomic::Ordering::Acquire)
+    }
+
+    fn guard(&self) -> ConnectionGuard {
+        self.count.fetch_add(1, std::sync::atomic::Ordering::AcqRel);
+        ConnectionGuard(self.clone())
+    }
+
+    /// Returns at once if fewer than `max` connections are live. Otherwise
+    /// waits until the count is below `max` minus a margin of
+    /// `max / RESUME_WATERMARK_FRACTION` (at least 1), so the accept loop
+    /// doesn't immediately pause again on the next accept.
+    async fn wait_for_capacity(&self, max: usize) {
+        // Clamp a `0` limit to 1, the same way `select` does for
+        // `max_concurrent_handshakes`, so a `0` limit admits one live
+        // connection at a time.
+        let max = max.max(1);
+
+        if self.live() < max {
+            return;
+        }
+
+        let margin = (max / RESUME_WATERMARK_FRACTION).max(1);
+        let resume_below = max.saturating_sub(margin).max(1);
+        while self.live() >= resume_below {
+            self.resumed.notified().await;
+        }
+    }
+}
+
+/// Decrements the shared connection count when the connection it is
+/// attached to (via [`ServerIo`]) is dropped.
+struct ConnectionGuard(ConnectionTracker);
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0
+            .count
+            .fetch_sub(1, std::sync::atomic::Ordering::AcqRel);
+        self.0.resumed.notify_one();
+    }
+}
+
+/// An accepted connection, together with the [`ConnectInfo`] captured for
+/// it. The live-connection guard lives inside `io` itself (see
+/// [`CancellableIo`]), so it stays held for as long as the connection is
+/// reachable through any layer built on top of it.
+pub(crate) struct Accepted<IO> {
+    pub(crate) io: ServerIo<IO>,
+    pub(crate) connect_info: ConnectInfo,
+}
+
+/// Inserts a connection's [`ConnectInfo`] into the extensions of every
+/// request dispatched on it.
+///
+/// `tcp_incoming` only captures `ConnectInfo` once per connection (see
+/// [`Accepted`]); `Server::serve_with_incoming` applies this layer once per
+/// accepted connection, so handlers can actually recover it via
+/// `req.extensions().get::<ConnectInfo>()` instead of it being discarded
+/// after accept.
+#[derive(Clone)]
+pub(crate) struct ConnectInfoLayer {
+    connect_info: ConnectInfo,
+}
+
+impl ConnectInfoLayer {
+    pub(crate) fn new(connect_info: ConnectInfo) -> Self {
+        Self { connect_info }
+    }
+}
+
+impl<S> Layer<S> for ConnectInfoLayer {
+    type Service = ConnectInfoService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectInfoService {
+            inner,
+            connect_info: self.connect_info.clone(),
+        }
+    }
+}
+
+/// See [`ConnectInfoLayer`].
+#[derive(Clone)]
+pub(crate) struct ConnectInfoService<S> {
+    inner: S,
+    connect_info: ConnectInfo,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for ConnectInfoService<S>
+where
+    S: Service<Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        req.extensions_mut().insert(self.connect_info.clone());
+        self.inner.call(req)
+    }
+}
+
+/// An asynchronous listener that accepts new connections.
+///
+/// Abstracting over this trait, rather than hard-coding `TcpListener`, lets
+/// [`tcp_incoming`] drive TCP, Unix domain sockets, and user-provided
+/// transports uniformly.
+///
+/// This is deliberately poll-based (`poll_accept`) rather than an `async fn
+/// accept` plus a separate `Connection` trait: the accept loop already
+/// drives everything else here (handshakes, shutdown, backpressure) from a
+/// single `poll`-based `Stream`, and a `poll_accept` implementation composes
+/// into that loop directly. Peer metadata is covered by [`Connected`]
+/// (`Self::Io: Connected`) instead of a distinct `Connection` trait, so a
+/// `Listener` only has to name its `Io` type rather than implement a second
+/// trait on top of it.
+pub trait Listener: Send {
+    /// The connection type produced by this listener.
+    type Io: AsyncRead + AsyncWrite + Connected + Unpin + Send + 'static;
+
+    /// Polls for a new connection, returning `None` once the listener is
+    /// exhausted (e.g. the bound socket was closed).
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<Option<io::Result<Self::Io>>>;
+}
+
+impl Listener for TcpIncoming {
+    type Io = TcpStream;
+
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<Option<io::Result<Self::Io>>> {
+        Pin::new(self).poll_next(cx)
+    }
+}
+
+/// Adapts a [`Listener`] into a [`Stream`], so the existing accept-loop
+/// machinery (built around streams of accepted connections) can drive any
+/// `Listener` implementation.
+struct AcceptStream<L>(L);
+
+impl<L: Listener + Unpin> Stream for AcceptStream<L> {
+    type Item = io::Result<L::Io>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().0.poll_accept(cx)
+    }
+}
 
 #[cfg(not(feature = "tls"))]
-pub(crate) fn tcp_incoming<IO, IE>(
-    incoming: impl Stream<Item = Result<IO, IE>>,
-) -> impl Stream<Item = Result<ServerIo<IO>, crate::BoxError>>
+pub(crate) fn tcp_incoming<L>(
+    listener: L,
+    limits: AcceptLimits,
+    shutdown: Option<ShutdownSignal>,
+) -> impl Stream<Item = Result<Accepted<CancellableIo<L::Io>>, crate::BoxError>>
 where
-    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
-    IE: Into<crate::BoxError>,
+    L: Listener + Unpin,
 {
     async_stream::try_stream! {
-        let mut incoming = pin!(incoming);
+        let mut incoming = pin!(AcceptStream(listener));
+        let connections = ConnectionTracker::new();
+        let shutdown = shutdown.unwrap_or_default();
+
+        loop {
+            // Enable the notification *before* checking the flag: `notify_waiters`
+            // stores no permit for waiters that haven't started waiting yet, so
+            // checking first would risk missing a `trigger()` that lands in
+            // between the check and the `select!` below.
+            let notified = shutdown.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if shutdown.is_triggered() {
+                break;
+            }
+
+            let next = async {
+                if let Some(max) = limits.max_connections {
+                    connections.wait_for_capacity(max).await;
+                }
+                incoming.next().await
+            };
+
+            let item = tokio::select! {
+                item = next => item,
+                _ = &mut notified => break,
+            };
+
+            let item = match item {
+                Some(item) => item,
+                None => break,
+            };
 
-        while let Some(item) = incoming.next().await {
             yield match item {
-                Ok(_) => item.map(ServerIo::new_io)?,
+                Ok(stream) => {
+                    let connect_info = stream.connect_info();
+                    let mut io = CancellableIo::new(stream, shutdown.clone());
+                    // No handshake to wait on here: accepted is established.
+                    io.establish(connections.guard());
+                    Accepted {
+                        io: ServerIo::new_io(io),
+                        connect_info,
+                    }
+                }
                 Err(e) => match handle_tcp_accept_error(e) {
                     ControlFlow::Continue(()) => continue,
                     ControlFlow::Break(e) => Err(e)?,
@@ -43,35 +703,77 @@ where
 }
 
 #[cfg(feature = "tls")]
-pub(crate) fn tcp_incoming<IO, IE>(
-    incoming: impl Stream<Item = Result<IO, IE>>,
+pub(crate) fn tcp_incoming<L>(
+    listener: L,
     tls: Option<TlsAcceptor>,
-) -> impl Stream<Item = Result<ServerIo<IO>, crate::BoxError>>
+    limits: AcceptLimits,
+    shutdown: Option<ShutdownSignal>,
+) -> impl Stream<Item = Result<Accepted<CancellableIo<L::Io>>, crate::BoxError>>
 where
-    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
-    IE: Into<crate::BoxError>,
+    L: Listener + Unpin,
 {
     async_stream::try_stream! {
-        let mut incoming = pin!(incoming);
+        let mut incoming = pin!(AcceptStream(listener));
 
         let mut tasks = tokio::task::JoinSet::new();
+        let connections = ConnectionTracker::new();
+        let shutdown = shutdown.unwrap_or_default();
 
         loop {
-            match select(&mut incoming, &mut tasks).await {
+            // See the matching comment in the non-TLS `tcp_incoming` above:
+            // enable the notification before checking the flag so a
+            // `trigger()` landing between the check and the `select!` below
+            // is never missed.
+            let notified = shutdown.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if shutdown.is_triggered() {
+                break;
+            }
+
+            let step = async {
+                if let Some(max) = limits.max_connections {
+                    connections.wait_for_capacity(max).await;
+                }
+                select(&mut incoming, &mut tasks, limits.max_concurrent_handshakes).await
+            };
+
+            let output = tokio::select! {
+                output = step => output,
+                _ = &mut notified => break,
+            };
+
+            match output {
                 SelectOutput::Incoming(stream) => {
+                    let connect_info = stream.connect_info();
+                    let stream = CancellableIo::new(stream, shutdown.clone());
+
                     if let Some(tls) = &tls {
                         let tls = tls.clone();
+                        // Only counted toward `max_connections` once the
+                        // handshake below actually succeeds: a connection
+                        // stuck handshaking must not hold capacity that a
+                        // connection waiting to be drained needs freed, or
+                        // the accept loop can park in `wait_for_capacity`
+                        // forever without ever reaching the `tasks.join_next()`
+                        // that would otherwise free it up.
+                        let connections = connections.clone();
                         tasks.spawn(async move {
-                            let io = tls.accept(stream).await?;
-                            Ok(ServerIo::new_tls_io(io))
+                            let mut io = tls.accept(stream).await?;
+                            io.get_mut().0.establish(connections.guard());
+                            let connect_info = tls_connect_info(&io, connect_info);
+                            Ok(Accepted { io: ServerIo::new_tls_io(io), connect_info })
                         });
                     } else {
-                        yield ServerIo::new_io(stream);
+                        let mut stream = stream;
+                        stream.establish(connections.guard());
+                        yield Accepted { io: ServerIo::new_io(stream), connect_info };
                     }
                 }
 
-                SelectOutput::Io(io) => {
-                    yield io;
+                SelectOutput::Io(accepted) => {
+                    yield accepted;
                 }
 
                 SelectOutput::TcpErr(e) => match handle_tcp_accept_error(e) {
@@ -92,7 +794,24 @@ where
     }
 }
 
-fn handle_tcp_accept_error(e: impl Into<crate::error::BoxError>) -> ControlFlow<crate::error::BoxError> {
+/// Fills in the TLS-specific fields of a [`ConnectInfo`] captured at accept
+/// time, once the handshake has completed and the session is available.
+#[cfg(feature = "tls")]
+fn tls_connect_info<IO>(
+    io: &tokio_rustls::server::TlsStream<IO>,
+    mut connect_info: ConnectInfo,
+) -> ConnectInfo {
+    let (_, session) = io.get_ref();
+    connect_info.alpn_protocol = session.alpn_protocol().map(ToOwned::to_owned);
+    connect_info.certificates = session
+        .peer_certificates()
+        .map(|certs| Arc::new(certs.to_vec()));
+    connect_info
+}
+
+fn handle_tcp_accept_error(
+    e: impl Into<crate::error::BoxError>,
+) -> ControlFlow<crate::error::BoxError> {
     let e = e.into();
     tracing::debug!(error = %e, "accept loop error");
     if let Some(e) = e.downcast_ref::<io::Error>() {
@@ -115,11 +834,31 @@ fn handle_tcp_accept_error(e: impl Into<crate::error::BoxError>) -> ControlFlow<
 #[cfg(feature = "tls")]
 async fn select<IO: 'static, IE>(
     incoming: &mut (impl Stream<Item = Result<IO, IE>> + Unpin),
-    tasks: &mut tokio::task::JoinSet<Result<ServerIo<IO>, crate::BoxError>>,
+    tasks: &mut tokio::task::JoinSet<Result<Accepted<CancellableIo<IO>>, crate::BoxError>>,
+    max_concurrent_handshakes: Option<usize>,
 ) -> SelectOutput<IO>
 where
     IE: Into<crate::BoxError>,
 {
+    // Clamp a `0` limit to 1: it's a nonsensical config (it would mean "no
+    // handshake may ever be in flight"), and treating it literally would
+    // make `tasks.len() >= 0` true even with an empty `tasks`, so we'd try
+    // to join a task that doesn't exist below.
+    let at_handshake_limit = max_concurrent_handshakes
+        .map(|max| max.max(1))
+        .is_some_and(|max| tasks.len() >= max);
+
+    // Once we're at the handshake cap, stop polling the listener entirely
+    // and only drain in-flight handshakes until we're back under it.
+    if at_handshake_limit {
+        return match tasks.join_next().await {
+            Some(Ok(Ok(io))) => SelectOutput::Io(io),
+            Some(Ok(Err(e))) => SelectOutput::TlsErr(e),
+            Some(Err(e)) => SelectOutput::TlsErr(e.into()),
+            None => unreachable!("at_handshake_limit implies tasks is non-empty"),
+        };
+    }
+
     if tasks.is_empty() {
         return match incoming.try_next().await {
             Ok(Some(stream)) => SelectOutput::Incoming(stream),
@@ -150,7 +889,7 @@ where
 #[cfg(feature = "tls")]
 enum SelectOutput<A> {
     Incoming(A),
-    Io(ServerIo<A>),
+    Io(Accepted<CancellableIo<A>>),
     TcpErr(crate::BoxError),
     TlsErr(crate::BoxError),
     Done,
@@ -274,4 +1013,214 @@ mod tests {
         }
         let _t3 = TcpIncoming::new(addr, true, None).unwrap();
     }
+
+    #[tokio::test]
+    async fn cancellable_io_waits_for_in_flight_request_before_closing() {
+        use std::task::{Context, Waker};
+
+        use tokio::io::{AsyncRead, ReadBuf};
+
+        use super::{CancellableIo, ShutdownSignal};
+
+        let (inner, _keep_open) = tokio::io::duplex(64);
+        let shutdown = ShutdownSignal::new();
+        let mut io = Box::pin(CancellableIo::new(inner, shutdown.clone()));
+
+        let guard = io.active_requests().guard();
+        shutdown.trigger();
+
+        let waker = Waker::noop();
+        let mut cx = Context::from_waker(waker);
+        let mut buf = [0u8; 8];
+        let mut read_buf = ReadBuf::new(&mut buf);
+
+        // A request is still in flight, so the connection must not be cut
+        // just because shutdown fired.
+        assert!(io.as_mut().poll_read(&mut cx, &mut read_buf).is_pending());
+
+        drop(guard);
+
+        // The guard's drop must wake this up: once nothing is in flight,
+        // shutdown can finally close the connection.
+        assert!(io.as_mut().poll_read(&mut cx, &mut read_buf).is_ready());
+    }
+
+    #[tokio::test]
+    async fn enabled_notified_does_not_miss_a_notify_before_its_first_poll() {
+        use std::{
+            future::Future,
+            sync::Arc,
+            task::{Context, Waker},
+        };
+
+        use super::EnabledNotified;
+
+        let notify = Arc::new(tokio::sync::Notify::new());
+        let mut waiting = EnabledNotified::new(notify.clone());
+
+        // `EnabledNotified::new` must register the wait immediately, not on
+        // first poll: a `notify_waiters()` landing here, before `waiting`
+        // has ever been polled, must still be seen.
+        notify.notify_waiters();
+
+        let waker = Waker::noop();
+        let mut cx = Context::from_waker(waker);
+        assert!(waiting.as_mut().poll(&mut cx).is_ready());
+    }
+
+    #[tokio::test]
+    async fn idle_notified_does_not_miss_a_guard_drop_before_its_first_poll() {
+        use std::{
+            future::Future,
+            task::{Context, Waker},
+        };
+
+        use super::ActiveRequests;
+
+        let active_requests = ActiveRequests::default();
+        let guard = active_requests.guard();
+        let mut idle = active_requests.idle_notified();
+
+        // `idle_notified()` must register the wait immediately, not on
+        // first poll: a guard drop landing here, before `idle` has ever
+        // been polled, must still be seen.
+        drop(guard);
+
+        let waker = Waker::noop();
+        let mut cx = Context::from_waker(waker);
+        assert!(idle.as_mut().poll(&mut cx).is_ready());
+    }
+
+    #[tokio::test]
+    async fn connection_tracker_resumes_at_the_watermark_not_on_the_first_drop() {
+        use super::ConnectionTracker;
+
+        let tracker = ConnectionTracker::new();
+        let max = 10;
+        let guards: Vec<_> = std::iter::repeat_with(|| tracker.guard())
+            .take(max)
+            .collect();
+
+        let waiter = tokio::spawn({
+            let tracker = tracker.clone();
+            async move { tracker.wait_for_capacity(max).await }
+        });
+
+        // Let the waiter run and park at the limit (10 live) before any
+        // connection closes.
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        let mut guards = guards.into_iter();
+        // margin = max(10 / RESUME_WATERMARK_FRACTION, 1) = 1, so dropping a
+        // single connection (down to 9 live) must not resume the waiter.
+        drop(guards.next().unwrap());
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        // Dropping past the watermark (down to 8 live) must resume it.
+        drop(guards.next().unwrap());
+        waiter.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn connection_tracker_clamps_a_zero_limit_to_one() {
+        use super::ConnectionTracker;
+
+        let tracker = ConnectionTracker::new();
+        let guard = tracker.guard();
+
+        let waiter = tokio::spawn({
+            let tracker = tracker.clone();
+            async move { tracker.wait_for_capacity(0).await }
+        });
+
+        // A `0` limit is clamped to 1: with one connection already live,
+        // the accept loop must pause until that connection closes.
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        drop(guard);
+        waiter.await.unwrap();
+    }
+
+    #[cfg(feature = "uds")]
+    #[tokio::test]
+    async fn unix_incoming_listener_accepts_connection() {
+        use std::{future::poll_fn, task::Poll};
+
+        use super::{Connected, Listener, UnixIncoming};
+
+        let path =
+            std::env::temp_dir().join(format!("tonic-rustls-uds-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut listener = UnixIncoming::new(&path).unwrap();
+        let _client = tokio::net::UnixStream::connect(&path).await.unwrap();
+
+        let accepted = poll_fn(|cx| match listener.poll_accept(cx) {
+            Poll::Ready(Some(Ok(io))) => Poll::Ready(io),
+            Poll::Ready(other) => panic!("expected an accepted connection, got {other:?}"),
+            Poll::Pending => Poll::Pending,
+        })
+        .await;
+
+        assert!(accepted.connect_info().remote_addr().is_none());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
+
+/// Binds a Unix domain socket for a [Server](super::Server).
+///
+/// An incoming stream, usable with [Server::serve_with_incoming](super::Server::serve_with_incoming),
+/// of `AsyncRead + AsyncWrite` that communicate with clients that connect
+/// over a Unix domain socket. Useful for local IPC without the overhead of
+/// a TCP loopback connection.
+#[cfg(feature = "uds")]
+#[derive(Debug)]
+pub struct UnixIncoming {
+    inner: tokio_stream::wrappers::UnixListenerStream,
+}
+
+#[cfg(feature = "uds")]
+impl UnixIncoming {
+    /// Creates an instance by binding (opening) the specified path.
+    pub fn new(path: impl AsRef<std::path::Path>) -> Result<Self, crate::BoxError> {
+        Self::from_listener(tokio::net::UnixListener::bind(path)?)
+    }
+
+    /// Creates a new `UnixIncoming` from an existing `tokio::net::UnixListener`.
+    pub fn from_listener(listener: tokio::net::UnixListener) -> Result<Self, crate::BoxError> {
+        Ok(Self {
+            inner: tokio_stream::wrappers::UnixListenerStream::new(listener),
+        })
+    }
+}
+
+#[cfg(feature = "uds")]
+impl Stream for UnixIncoming {
+    type Item = io::Result<tokio::net::UnixStream>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
+
+#[cfg(feature = "uds")]
+impl Listener for UnixIncoming {
+    type Io = tokio::net::UnixStream;
+
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<Option<io::Result<Self::Io>>> {
+        Pin::new(self).poll_next(cx)
+    }
+}
+
+// Unix domain sockets have no internet socket address to report, so
+// `ConnectInfo::remote_addr` is always `None` for these connections.
+#[cfg(feature = "uds")]
+impl Connected for tokio::net::UnixStream {
+    fn connect_info(&self) -> ConnectInfo {
+        ConnectInfo::default()
+    }
 }
@@ -0,0 +1,241 @@
+//! The gRPC server.
+
+use std::{pin::pin, time::Duration};
+
+use http::{Request, Response};
+use hyper::{body::Incoming, server::conn::http2};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    service::TowerToHyperService,
+};
+use tokio_stream::StreamExt;
+use tower::{Layer, Service};
+
+mod incoming;
+mod service;
+
+#[cfg(feature = "uds")]
+pub use incoming::UnixIncoming;
+pub use incoming::{ConnectInfo, Connected, Listener, ShutdownSignal, TcpIncoming};
+
+use incoming::{ActiveRequestsLayer, ConnectInfoLayer};
+use service::RecoverError;
+
+/// A configured gRPC server, ready to accept connections.
+///
+/// Obtained via [`Builder::build`].
+#[derive(Debug, Default)]
+pub struct Server {
+    limits: incoming::AcceptLimits,
+    drain_deadline: Option<Duration>,
+    #[cfg(feature = "tls")]
+    tls: Option<service::TlsAcceptor>,
+}
+
+impl Server {
+    /// Returns a [`Builder`] for configuring a [`Server`].
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// Accepts connections from `listener`, dispatching every request
+    /// received on them to `service`, until the listener is exhausted.
+    ///
+    /// Each connection is handled with the TLS and backpressure settings
+    /// configured on the [`Builder`] this [`Server`] was built from. Every
+    /// request carries the connection's [`ConnectInfo`] in its extensions,
+    /// recoverable via `req.extensions().get::<ConnectInfo>()`.
+    ///
+    /// Equivalent to [`Server::serve_with_shutdown`] with a signal that is
+    /// never triggered.
+    pub async fn serve_with_incoming<L, S, ResBody>(
+        &self,
+        listener: L,
+        service: S,
+    ) -> Result<(), crate::BoxError>
+    where
+        L: Listener + Unpin,
+        S: Service<Request<Incoming>, Response = Response<ResBody>> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<crate::BoxError>,
+        ResBody: http_body::Body + Send + 'static,
+        ResBody::Data: Send,
+        ResBody::Error: Into<crate::BoxError>,
+    {
+        self.serve_with_shutdown(listener, service, None).await
+    }
+
+    /// Like [`Server::serve_with_incoming`], but stops accepting and drains
+    /// in-flight connections once `signal` is triggered.
+    ///
+    /// A connection with no request in flight is cut as soon as shutdown
+    /// fires; one being actively served is given until its response
+    /// finishes, or until [`Builder::drain_deadline`] elapses, whichever
+    /// comes first — any connections still running at that point are
+    /// aborted rather than awaited forever. See [`ShutdownSignal`].
+    pub async fn serve_with_shutdown<L, S, ResBody>(
+        &self,
+        listener: L,
+        service: S,
+        signal: impl Into<Option<ShutdownSignal>>,
+    ) -> Result<(), crate::BoxError>
+    where
+        L: Listener + Unpin,
+        S: Service<Request<Incoming>, Response = Response<ResBody>> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<crate::BoxError>,
+        ResBody: http_body::Body + Send + 'static,
+        ResBody::Data: Send,
+        ResBody::Error: Into<crate::BoxError>,
+    {
+        let signal = signal.into();
+
+        #[cfg(feature = "tls")]
+        let mut accepted = pin!(incoming::tcp_incoming(
+            listener,
+            self.tls.clone(),
+            self.limits,
+            signal,
+        ));
+        #[cfg(not(feature = "tls"))]
+        let mut accepted = pin!(incoming::tcp_incoming(listener, self.limits, signal));
+
+        let mut connections = tokio::task::JoinSet::new();
+
+        while let Some(accepted) = accepted.next().await {
+            let incoming::Accepted { io, connect_info } = accepted?;
+            let active_requests = io.active_requests();
+            let service = ConnectInfoLayer::new(connect_info).layer(
+                ActiveRequestsLayer::new(active_requests).layer(RecoverError::new(service.clone())),
+            );
+
+            connections.spawn(async move {
+                let io = TokioIo::new(io);
+                let service = TowerToHyperService::new(service);
+                if let Err(error) = http2::Builder::new(TokioExecutor::new())
+                    .serve_connection(io, service)
+                    .await
+                {
+                    tracing::debug!(%error, "connection error");
+                }
+            });
+        }
+
+        // The listener is exhausted or shutdown fired: let every connection already handed off
+        // finish before reporting that we're done, up to `drain_deadline`.
+        match self.drain_deadline {
+            Some(deadline) => {
+                let drained = tokio::time::timeout(deadline, async {
+                    while connections.join_next().await.is_some() {}
+                })
+                .await
+                .is_ok();
+                if !drained {
+                    tracing::debug!(
+                        ?deadline,
+                        "drain deadline elapsed; aborting remaining connections"
+                    );
+                    connections.shutdown().await;
+                }
+            }
+            None => while connections.join_next().await.is_some() {},
+        }
+
+        Ok(())
+    }
+}
+
+/// Configures and builds a [`Server`].
+#[derive(Debug, Default)]
+pub struct Builder {
+    limits: incoming::AcceptLimits,
+    drain_deadline: Option<Duration>,
+    #[cfg(feature = "tls")]
+    tls: Option<service::TlsAcceptor>,
+    /// Set by [`Builder::tls_handshake_timeout`]; applied to `tls` as soon as
+    /// both it and a config are available, regardless of which is set first.
+    #[cfg(feature = "tls")]
+    handshake_timeout: Option<Option<Duration>>,
+}
+
+impl Builder {
+    /// Enables TLS for incoming connections, using `config` to perform the
+    /// server-side handshake.
+    ///
+    /// The handshake has a default timeout; see
+    /// [`Builder::tls_handshake_timeout`] to override it.
+    #[cfg(feature = "tls")]
+    pub fn tls_config(
+        mut self,
+        config: tokio_rustls::rustls::ServerConfig,
+    ) -> Result<Self, crate::BoxError> {
+        let mut tls = service::TlsAcceptor::new(config)?;
+        if let Some(timeout) = self.handshake_timeout {
+            tls.handshake_timeout(timeout);
+        }
+        self.tls = Some(tls);
+        Ok(self)
+    }
+
+    /// Sets the deadline for completing a TLS handshake. `None` disables the
+    /// timeout entirely.
+    ///
+    /// May be called before or after [`Builder::tls_config`]; either way, it
+    /// takes effect once TLS is actually configured.
+    #[cfg(feature = "tls")]
+    pub fn tls_handshake_timeout(mut self, timeout: Option<Duration>) -> Self {
+        if let Some(tls) = &mut self.tls {
+            tls.handshake_timeout(timeout);
+        }
+        self.handshake_timeout = Some(timeout);
+        self
+    }
+
+    /// Bounds how many TLS handshakes the accept loop will drive
+    /// concurrently. Once reached, it stops polling the listener for new
+    /// connections and only drains in-flight handshakes until the count
+    /// drops back below the limit.
+    ///
+    /// Only available with the `tls` feature, and has no effect unless TLS
+    /// is configured via [`Builder::tls_config`], since only the TLS accept
+    /// path spawns a handshake per connection.
+    #[cfg(feature = "tls")]
+    pub fn max_concurrent_handshakes(mut self, max: usize) -> Self {
+        self.limits.max_concurrent_handshakes = Some(max);
+        self
+    }
+
+    /// Bounds the number of live, already-established connections. Once
+    /// reached, the accept loop pauses entirely (it stops polling the
+    /// listener) until enough connections close to resume.
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.limits.max_connections = Some(max);
+        self
+    }
+
+    /// Bounds how long [`Server::serve_with_shutdown`] waits, once shutdown
+    /// fires, for connections still draining to finish. Once the deadline
+    /// elapses, every connection still running is aborted instead of
+    /// awaited further. `None` (the default) waits however long it takes.
+    pub fn drain_deadline(mut self, deadline: Option<Duration>) -> Self {
+        self.drain_deadline = deadline;
+        self
+    }
+
+    /// The configured accept-loop backpressure limits, for the code that
+    /// drives `tcp_incoming`.
+    pub(crate) fn accept_limits(&self) -> incoming::AcceptLimits {
+        self.limits
+    }
+
+    /// Finalizes the configuration into a [`Server`] ready to accept
+    /// connections.
+    pub fn build(self) -> Server {
+        Server {
+            limits: self.accept_limits(),
+            drain_deadline: self.drain_deadline,
+            #[cfg(feature = "tls")]
+            tls: self.tls,
+        }
+    }
+}
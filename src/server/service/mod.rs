@@ -0,0 +1,95 @@
+mod recover_error;
+#[cfg(feature = "tls")]
+mod tls;
+
+pub(crate) use recover_error::RecoverError;
+#[cfg(feature = "tls")]
+pub(crate) use tls::TlsAcceptor;
+
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use pin_project::pin_project;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+#[cfg(feature = "tls")]
+use tokio_rustls::server::TlsStream;
+
+use super::incoming::{ActiveRequests, CancellableIo};
+
+/// The I/O type handed to the per-connection HTTP/2 service: either a plain
+/// accepted connection, or one that has completed a TLS handshake.
+#[pin_project(project = ServerIoProj)]
+pub(crate) enum ServerIo<IO> {
+    Io(#[pin] CancellableIo<IO>),
+    #[cfg(feature = "tls")]
+    TlsIo(#[pin] TlsStream<CancellableIo<IO>>),
+}
+
+impl<IO> ServerIo<IO> {
+    pub(crate) fn new_io(io: CancellableIo<IO>) -> Self {
+        Self::Io(io)
+    }
+
+    #[cfg(feature = "tls")]
+    pub(crate) fn new_tls_io(io: TlsStream<CancellableIo<IO>>) -> Self {
+        Self::TlsIo(io)
+    }
+
+    /// Returns a handle for tracking requests in flight on this connection,
+    /// for [`ActiveRequestsLayer`](super::incoming::ActiveRequestsLayer) to
+    /// apply to the per-connection dispatch service.
+    pub(crate) fn active_requests(&self) -> ActiveRequests {
+        match self {
+            Self::Io(io) => io.active_requests(),
+            #[cfg(feature = "tls")]
+            Self::TlsIo(io) => io.get_ref().0.active_requests(),
+        }
+    }
+}
+
+impl<IO: AsyncRead + AsyncWrite> AsyncRead for ServerIo<IO> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.project() {
+            ServerIoProj::Io(io) => io.poll_read(cx, buf),
+            #[cfg(feature = "tls")]
+            ServerIoProj::TlsIo(io) => io.poll_read(cx, buf),
+        }
+    }
+}
+
+impl<IO: AsyncRead + AsyncWrite> AsyncWrite for ServerIo<IO> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.project() {
+            ServerIoProj::Io(io) => io.poll_write(cx, buf),
+            #[cfg(feature = "tls")]
+            ServerIoProj::TlsIo(io) => io.poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.project() {
+            ServerIoProj::Io(io) => io.poll_flush(cx),
+            #[cfg(feature = "tls")]
+            ServerIoProj::TlsIo(io) => io.poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.project() {
+            ServerIoProj::Io(io) => io.poll_shutdown(cx),
+            #[cfg(feature = "tls")]
+            ServerIoProj::TlsIo(io) => io.poll_shutdown(cx),
+        }
+    }
+}
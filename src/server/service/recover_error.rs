@@ -1,4 +1,3 @@
-use tonic::Status;
 use http::Response;
 use http_body::Frame;
 use pin_project::pin_project;
@@ -7,6 +6,7 @@ use std::{
     pin::Pin,
     task::{ready, Context, Poll},
 };
+use tonic::Status;
 use tower::Service;
 
 /// Middleware that attempts to recover from service errors by turning them into a response built
@@ -1,13 +1,19 @@
-use std::{fmt, sync::Arc};
+use std::{fmt, sync::Arc, time::Duration};
 
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_rustls::{rustls::ServerConfig, server::TlsStream, TlsAcceptor as RustlsAcceptor};
 
 use crate::service::ALPN_H2;
 
+/// The default deadline for completing a TLS handshake, chosen to be
+/// generous for slow clients while still bounding how long a slow-loris
+/// connection can hold a handshake task open.
+pub(crate) const DEFAULT_TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Clone)]
 pub(crate) struct TlsAcceptor {
     inner: Arc<ServerConfig>,
+    handshake_timeout: Option<Duration>,
 }
 
 impl TlsAcceptor {
@@ -16,20 +22,37 @@ impl TlsAcceptor {
 
         Ok(Self {
             inner: Arc::new(config),
+            handshake_timeout: Some(DEFAULT_TLS_HANDSHAKE_TIMEOUT),
         })
     }
 
+    /// Sets the deadline for completing a TLS handshake. `None` disables the
+    /// timeout entirely.
+    pub(crate) fn handshake_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
     pub(crate) async fn accept<IO>(&self, io: IO) -> Result<TlsStream<IO>, crate::BoxError>
     where
         IO: AsyncRead + AsyncWrite + Unpin,
     {
         let acceptor = RustlsAcceptor::from(self.inner.clone());
-        acceptor.accept(io).await.map_err(Into::into)
+
+        match self.handshake_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, acceptor.accept(io))
+                .await
+                .map_err(|_| "tls handshake timed out")?
+                .map_err(Into::into),
+            None => acceptor.accept(io).await.map_err(Into::into),
+        }
     }
 }
 
 impl fmt::Debug for TlsAcceptor {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("TlsAcceptor").finish()
+        f.debug_struct("TlsAcceptor")
+            .field("handshake_timeout", &self.handshake_timeout)
+            .finish()
     }
 }